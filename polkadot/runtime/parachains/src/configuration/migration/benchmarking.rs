@@ -0,0 +1,46 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking for the `HostConfiguration` migrations.
+
+use crate::configuration::{migration::v9, Config};
+use frame_benchmarking::v2::*;
+
+#[benchmarks]
+mod benchmarks {
+	use super::*;
+
+	// The weight of migrating `HostConfiguration` from V8 to V9, as a function of the number of
+	// pending configuration upgrades queued in `PendingConfigs`. The active config is always
+	// migrated in addition to the `n` pending ones.
+	#[benchmark]
+	fn migrate_host_config(n: Linear<0, 1_000>) {
+		v9::benchmarking::build_pending_configs::<T>(n);
+
+		#[block]
+		{
+			v9::migrate_to_v9::<T>();
+		}
+
+		assert!(v9::benchmarking::active_config_is_set::<T>());
+	}
+
+	impl_benchmark_test_suite!(
+		Pallet,
+		crate::mock::new_test_ext(Default::default()),
+		crate::mock::Test,
+	);
+}
@@ -0,0 +1,74 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A macro shared by the `HostConfiguration` migrations to cut down on the copy-paste hazard of
+//! hand-written `translate` closures: every time a field is added or removed, every migration
+//! step had to be re-typed in full, and a single mis-copied or skipped field silently skews every
+//! field that comes after it (the exact failure mode `assert_eq!` comparisons in the migration
+//! tests exist to catch).
+//!
+//! [`migrate_host_config`] takes the list of fields that are identical between the two versions
+//! plus the delta (`added`/`removed`), and generates both the `translate` function used by the
+//! migration and a `assert_fields_preserved` helper driven by the very same field list, so the
+//! list only has to be maintained in one place.
+
+/// Generate a `translate` fn between two structurally similar `HostConfiguration` versions, plus
+/// a test helper that asserts every shared field survived the translation unchanged.
+///
+/// - `fields`: every field present, with the same name and type, on both `$old` and `$new`.
+/// - `added`: fields that only exist on `$new`; populated from the given expression.
+/// - `removed`: fields that only exist on `$old`; dropped during translation.
+macro_rules! migrate_host_config {
+	(
+		from $old:ident => $new:ident,
+		fields: [$($field:ident),* $(,)?],
+		added: { $($added_field:ident: $added_expr:expr),* $(,)? },
+		removed: { $($removed_field:ident),* $(,)? },
+	) => {
+		// Unusual formatting is justified:
+		// - this code is transient and will be removed after all migrations are done.
+		// - this code is important enough to optimize for legibility sacrificing consistency.
+		#[rustfmt::skip]
+		fn translate<BlockNumber>(pre: $old<BlockNumber>) -> $new<BlockNumber> {
+			// Referencing the removed fields here keeps this list honest: a typo or a field
+			// that was never actually removed fails to compile instead of being silently wrong.
+			$(let _ = &pre.$removed_field;)*
+
+			$new {
+				$($field: pre.$field,)*
+				$($added_field: $added_expr,)*
+			}
+		}
+
+		/// Asserts that every field named in `fields` above made it across the migration
+		/// unchanged. Driven by the same field list as `translate`, so it can't drift out of
+		/// sync with what the migration actually copies.
+		///
+		/// Used both by the unit tests and, under `try-runtime`, by `post_upgrade` - a single
+		/// routine instead of the ad-hoc assertions that used to live only in the tests.
+		#[cfg(any(test, feature = "try-runtime"))]
+		#[allow(dead_code)]
+		#[rustfmt::skip]
+		fn assert_fields_preserved<BlockNumber: PartialEq + sp_std::fmt::Debug + Clone>(
+			pre: &$old<BlockNumber>,
+			post: &$new<BlockNumber>,
+		) {
+			$(assert_eq!(pre.$field, post.$field);)*
+		}
+	};
+}
+
+pub(crate) use migrate_host_config;
@@ -0,0 +1,249 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Migrations for the configuration pallet.
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod macros;
+pub mod v9;
+
+use crate::configuration::{self, Config, Pallet};
+#[cfg(feature = "try-runtime")]
+use codec::{Decode, Encode};
+use frame_support::{traits::StorageVersion, weights::Weight};
+use sp_std::{boxed::Box, vec, vec::Vec};
+
+use frame_support::traits::OnRuntimeUpgrade;
+
+/// The latest storage version understood by [`MigrateToLatest`].
+///
+/// Bump this alongside adding a new entry to [`MigrateToLatest::steps`] whenever a new
+/// `MigrateToVN` is introduced.
+const LATEST_STORAGE_VERSION: u16 = 9;
+
+/// A single migration step, keyed by the storage version it migrates *from*. Also carries the
+/// same `pre_upgrade`/`post_upgrade` state-capture and consistency-check routines the step's own
+/// `MigrateToVN` type runs, so [`MigrateToLatest`] can apply the identical verification for
+/// whichever steps it actually runs.
+struct MigrationStep {
+	from: u16,
+	on_runtime_upgrade: Box<dyn Fn() -> Weight>,
+	#[cfg(feature = "try-runtime")]
+	pre_upgrade: Box<dyn Fn() -> Result<Vec<u8>, sp_runtime::TryRuntimeError>>,
+	#[cfg(feature = "try-runtime")]
+	post_upgrade: Box<dyn Fn(Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError>>,
+}
+
+/// Applies every registered migration step in order, from the storage version the chain is
+/// currently on up to [`LATEST_STORAGE_VERSION`], in a single `on_runtime_upgrade`.
+///
+/// Only the `v8->v9` step is registered in [`MigrateToLatest::steps`]. A chain on V8 is brought
+/// to V9; a chain already on V9 is left as it is. A chain on an earlier version (e.g. V6 or V7)
+/// is not migrated: the missing step is logged as a gap in the chain and the storage version
+/// stays where it was.
+pub struct MigrateToLatest<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> MigrateToLatest<T> {
+	/// The ordered registry of migration steps. Must be sorted by source version and must not
+	/// contain gaps; add a new entry here whenever [`LATEST_STORAGE_VERSION`] is bumped.
+	fn steps() -> Vec<MigrationStep> {
+		vec![MigrationStep {
+			from: 8,
+			on_runtime_upgrade: Box::new(v9::migrate_to_v9::<T>),
+			#[cfg(feature = "try-runtime")]
+			pre_upgrade: Box::new(v9::pre_upgrade_to_v9::<T>),
+			#[cfg(feature = "try-runtime")]
+			post_upgrade: Box::new(v9::post_upgrade_to_v9::<T>),
+		}]
+	}
+
+	/// Applies `steps` in order starting from `version`, returning the storage version the chain
+	/// ends up on and the total weight consumed. Doesn't touch `StorageVersion` itself, so tests
+	/// can drive it against an arbitrary starting version without going through pallet storage.
+	fn apply_steps(mut version: u16, steps: Vec<MigrationStep>) -> (u16, Weight) {
+		let mut weight = Weight::zero();
+
+		for step in steps {
+			if version < step.from {
+				// The registry is missing a step or is out of order: applying `step` now would
+				// translate the wrong source version. Skip it defensively and log loudly rather
+				// than silently corrupting storage.
+				log::warn!(
+					target: configuration::LOG_TARGET,
+					"HostConfiguration MigrateToLatest: found a gap in the migration chain, \
+					expected a step from v{} but next registered step is from v{}; skipping it",
+					version,
+					step.from,
+				);
+				continue
+			}
+
+			if version > step.from {
+				// Expected, steady-state behaviour: the chain already ran this step (or never
+				// needed it) in a previous `on_runtime_upgrade`, and `MigrateToLatest` is left
+				// wired into the runtime's migration tuple until every chain has caught up.
+				log::debug!(
+					target: configuration::LOG_TARGET,
+					"HostConfiguration MigrateToLatest: chain is already past v{}, skipping its step",
+					step.from,
+				);
+				continue
+			}
+
+			weight = weight.saturating_add((step.on_runtime_upgrade)());
+			version += 1;
+		}
+
+		(version, weight)
+	}
+}
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToLatest<T> {
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+		// Capture state only for the steps that will actually run, in the same order
+		// `on_runtime_upgrade` will run them, keyed by source version so `post_upgrade` can match
+		// each captured blob back to the step that produced it even though the storage version
+		// has moved on by the time `post_upgrade` runs.
+		let mut version: u16 = StorageVersion::get::<Pallet<T>>().into();
+		let mut captured: Vec<(u16, Vec<u8>)> = Vec::new();
+
+		for step in Self::steps() {
+			if version != step.from {
+				continue
+			}
+
+			captured.push((step.from, (step.pre_upgrade)()?));
+			version += 1;
+		}
+
+		Ok(captured.encode())
+	}
+
+	fn on_runtime_upgrade() -> Weight {
+		let version: u16 = StorageVersion::get::<Pallet<T>>().into();
+
+		log::info!(
+			target: configuration::LOG_TARGET,
+			"HostConfiguration MigrateToLatest running from storage version {}",
+			version,
+		);
+
+		let (version, weight) = Self::apply_steps(version, Self::steps());
+
+		if version != LATEST_STORAGE_VERSION {
+			log::warn!(
+				target: configuration::LOG_TARGET,
+				"HostConfiguration MigrateToLatest: chain is on v{} after running all \
+				applicable steps, but latest known version is v{}",
+				version,
+				LATEST_STORAGE_VERSION,
+			);
+		}
+
+		StorageVersion::new(version).put::<Pallet<T>>();
+		log::info!(
+			target: configuration::LOG_TARGET,
+			"HostConfiguration MigrateToLatest finished at storage version {}",
+			version,
+		);
+
+		weight
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+		let captured: Vec<(u16, Vec<u8>)> = Decode::decode(&mut &state[..])
+			.map_err(|_| "failed to decode pre-upgrade state of HostConfiguration MigrateToLatest")?;
+
+		for (from, state) in captured {
+			let step = Self::steps()
+				.into_iter()
+				.find(|step| step.from == from)
+				.ok_or("HostConfiguration MigrateToLatest: a captured step is no longer registered")?;
+			(step.post_upgrade)(state)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn step(from: u16, weight: Weight) -> MigrationStep {
+		MigrationStep {
+			from,
+			on_runtime_upgrade: Box::new(move || weight),
+			#[cfg(feature = "try-runtime")]
+			pre_upgrade: Box::new(|| Ok(Vec::new())),
+			#[cfg(feature = "try-runtime")]
+			post_upgrade: Box::new(|_| Ok(())),
+		}
+	}
+
+	#[test]
+	fn applies_a_step_on_exact_version_match() {
+		let (version, weight) = MigrateToLatest::<crate::mock::Test>::apply_steps(
+			8,
+			vec![step(8, Weight::from_parts(100, 0))],
+		);
+
+		assert_eq!(version, 9);
+		assert_eq!(weight, Weight::from_parts(100, 0));
+	}
+
+	#[test]
+	fn skips_a_step_the_chain_is_already_past() {
+		let (version, weight) = MigrateToLatest::<crate::mock::Test>::apply_steps(
+			9,
+			vec![step(8, Weight::from_parts(100, 0))],
+		);
+
+		assert_eq!(version, 9);
+		assert_eq!(weight, Weight::zero());
+	}
+
+	#[test]
+	fn skips_a_gap_without_advancing_the_version() {
+		let (version, weight) = MigrateToLatest::<crate::mock::Test>::apply_steps(
+			7,
+			vec![step(8, Weight::from_parts(100, 0))],
+		);
+
+		// The chain is behind the only registered step's source version: there's nothing
+		// applicable for it yet, so it must be left exactly where it was rather than skipped
+		// past.
+		assert_eq!(version, 7);
+		assert_eq!(weight, Weight::zero());
+	}
+
+	#[test]
+	fn sums_weight_across_multiple_steps() {
+		let (version, weight) = MigrateToLatest::<crate::mock::Test>::apply_steps(
+			7,
+			vec![
+				step(7, Weight::from_parts(100, 0)),
+				step(8, Weight::from_parts(10, 0)),
+			],
+		);
+
+		assert_eq!(version, 9);
+		assert_eq!(weight, Weight::from_parts(110, 0));
+	}
+}
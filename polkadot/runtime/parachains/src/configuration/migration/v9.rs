@@ -29,6 +29,7 @@ use sp_std::vec::Vec;
 use frame_support::traits::OnRuntimeUpgrade;
 
 use super::v8::V8HostConfiguration;
+use crate::configuration::weights::WeightInfo;
 type V9HostConfiguration<BlockNumber> = configuration::HostConfiguration<BlockNumber>;
 
 mod v8 {
@@ -61,12 +62,87 @@ mod v9 {
 	>;
 }
 
+/// Checks that a just-migrated config is internally consistent, under `try-runtime`.
+///
+/// We don't silently clamp an inconsistent config to a default here (see
+/// `sanitize_host_config`), so a config that fails its own sanity check means the translation
+/// produced something the chain would reject at the next session boundary.
+#[cfg(feature = "try-runtime")]
+fn ensure_migrated_config_consistent<T: Config>(
+	config: &V9HostConfiguration<BlockNumberFor<T>>,
+	err_msg: &'static str,
+) -> Result<(), sp_runtime::TryRuntimeError> {
+	config.check_consistency().map_err(|_| err_msg)?;
+	Ok(())
+}
+
+/// Captures the complete pre-migration (V8) state, so [`post_upgrade_to_v9`] can verify, field by
+/// field, that the translation didn't drop or mis-assign anything.
+///
+/// Standalone so that [`super::MigrateToLatest`] can run the same state capture for its `v8 -> v9`
+/// step as [`MigrateToV9`] does for itself.
+#[cfg(feature = "try-runtime")]
+pub(crate) fn pre_upgrade_to_v9<T: Config>() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+	let active = v8::ActiveConfig::<T>::get();
+	let pending = v8::PendingConfigs::<T>::get().unwrap_or_default();
+	Ok((active, pending).encode())
+}
+
+/// Verifies, against the state captured by [`pre_upgrade_to_v9`], that every shared field
+/// survived the `v8 -> v9` translation and that the result is internally consistent.
+///
+/// Standalone for the same reason as [`pre_upgrade_to_v9`].
+#[cfg(feature = "try-runtime")]
+pub(crate) fn post_upgrade_to_v9<T: Config>(
+	state: Vec<u8>,
+) -> Result<(), sp_runtime::TryRuntimeError> {
+	let (pre_active, pre_pending): (
+		Option<V8HostConfiguration<BlockNumberFor<T>>>,
+		Vec<(SessionIndex, V8HostConfiguration<BlockNumberFor<T>>)>,
+	) = Decode::decode(&mut &state[..])
+		.map_err(|_| "failed to decode pre-upgrade state of HostConfiguration MigrateToV9")?;
+
+	// `migrate_to_v9` always writes an `ActiveConfig`, even when there was nothing to
+	// translate from (falling back to `V8HostConfiguration::default()`), so this must be
+	// checked unconditionally rather than only when `pre_active` is `Some`.
+	let post_active = v9::ActiveConfig::<T>::get()
+		.ok_or("ActiveConfig missing after HostConfiguration MigrateToV9")?;
+	if let Some(pre_active) = pre_active {
+		assert_fields_preserved(&pre_active, &post_active);
+	}
+	ensure_migrated_config_consistent::<T>(
+		&post_active,
+		"ActiveConfig migrated to V9 is not internally consistent",
+	)?;
+
+	let post_pending = v9::PendingConfigs::<T>::get().unwrap_or_default();
+	ensure!(
+		pre_pending.len() == post_pending.len(),
+		"number of PendingConfigs changed across HostConfiguration MigrateToV9"
+	);
+	for ((pre_session, pre_config), (post_session, post_config)) in
+		pre_pending.iter().zip(post_pending.iter())
+	{
+		ensure!(
+			pre_session == post_session,
+			"session index of a pending config changed across HostConfiguration MigrateToV9"
+		);
+		assert_fields_preserved(pre_config, post_config);
+		ensure_migrated_config_consistent::<T>(
+			post_config,
+			"a pending config migrated to V9 is not internally consistent",
+		)?;
+	}
+
+	Ok(())
+}
+
 pub struct MigrateToV9<T>(sp_std::marker::PhantomData<T>);
 impl<T: Config> OnRuntimeUpgrade for MigrateToV9<T> {
 	#[cfg(feature = "try-runtime")]
 	fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
 		log::trace!(target: crate::configuration::LOG_TARGET, "Running pre_upgrade() for HostConfiguration MigrateToV9");
-		Ok(Vec::new())
+		pre_upgrade_to_v9::<T>()
 	}
 
 	fn on_runtime_upgrade() -> Weight {
@@ -85,78 +161,101 @@ impl<T: Config> OnRuntimeUpgrade for MigrateToV9<T> {
 	}
 
 	#[cfg(feature = "try-runtime")]
-	fn post_upgrade(_state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+	fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
 		log::trace!(target: crate::configuration::LOG_TARGET, "Running post_upgrade() for HostConfiguration MigrateToV9");
 		ensure!(
 			StorageVersion::get::<Pallet<T>>() >= 9,
 			"Storage version should be >= 9 after the migration"
 		);
 
-		Ok(())
+		post_upgrade_to_v9::<T>(state)
 	}
 }
 
-fn migrate_to_v9<T: Config>() -> Weight {
-	// Unusual formatting is justified:
-	// - make it easier to verify that fields assign what they supposed to assign.
-	// - this code is transient and will be removed after all migrations are done.
-	// - this code is important enough to optimize for legibility sacrificing consistency.
-	#[rustfmt::skip]
-	let translate =
-		|pre: V8HostConfiguration<BlockNumberFor<T>>| ->
-		V9HostConfiguration<BlockNumberFor<T>>
-	{
-		V9HostConfiguration {
-max_code_size                            : pre.max_code_size,
-max_head_data_size                       : pre.max_head_data_size,
-max_upward_queue_count                   : pre.max_upward_queue_count,
-max_upward_queue_size                    : pre.max_upward_queue_size,
-max_upward_message_size                  : pre.max_upward_message_size,
-max_upward_message_num_per_candidate     : pre.max_upward_message_num_per_candidate,
-hrmp_max_message_num_per_candidate       : pre.hrmp_max_message_num_per_candidate,
-validation_upgrade_cooldown              : pre.validation_upgrade_cooldown,
-validation_upgrade_delay                 : pre.validation_upgrade_delay,
-max_pov_size                             : pre.max_pov_size,
-max_downward_message_size                : pre.max_downward_message_size,
-hrmp_sender_deposit                      : pre.hrmp_sender_deposit,
-hrmp_recipient_deposit                   : pre.hrmp_recipient_deposit,
-hrmp_channel_max_capacity                : pre.hrmp_channel_max_capacity,
-hrmp_channel_max_total_size              : pre.hrmp_channel_max_total_size,
-hrmp_max_parachain_inbound_channels      : pre.hrmp_max_parachain_inbound_channels,
-hrmp_max_parachain_outbound_channels     : pre.hrmp_max_parachain_outbound_channels,
-hrmp_channel_max_message_size            : pre.hrmp_channel_max_message_size,
-code_retention_period                    : pre.code_retention_period,
-on_demand_cores                          : pre.on_demand_cores,
-on_demand_retries                        : pre.on_demand_retries,
-group_rotation_frequency                 : pre.group_rotation_frequency,
-paras_availability_period                : pre.paras_availability_period,
-scheduling_lookahead                     : pre.scheduling_lookahead,
-max_validators_per_core                  : pre.max_validators_per_core,
-max_validators                           : pre.max_validators,
-dispute_period                           : pre.dispute_period,
-dispute_post_conclusion_acceptance_period: pre.dispute_post_conclusion_acceptance_period,
-no_show_slots                            : pre.no_show_slots,
-n_delay_tranches                         : pre.n_delay_tranches,
-zeroth_delay_tranche_width               : pre.zeroth_delay_tranche_width,
-needed_approvals                         : pre.needed_approvals,
-relay_vrf_modulo_samples                 : pre.relay_vrf_modulo_samples,
-pvf_voting_ttl                           : pre.pvf_voting_ttl,
-minimum_validation_upgrade_delay         : pre.minimum_validation_upgrade_delay,
-async_backing_params                     : pre.async_backing_params,
-executor_params                          : pre.executor_params,
-on_demand_queue_max_size                 : pre.on_demand_queue_max_size,
-on_demand_base_fee                       : pre.on_demand_base_fee,
-on_demand_fee_variability                : pre.on_demand_fee_variability,
-on_demand_target_queue_utilization       : pre.on_demand_target_queue_utilization,
-on_demand_ttl                            : pre.on_demand_ttl,
-minimum_backing_votes                    : LEGACY_MIN_BACKING_VOTES
-		}
-	};
+use super::macros::migrate_host_config;
+
+migrate_host_config!(
+	from V8HostConfiguration => V9HostConfiguration,
+	fields: [
+		max_code_size,
+		max_head_data_size,
+		max_upward_queue_count,
+		max_upward_queue_size,
+		max_upward_message_size,
+		max_upward_message_num_per_candidate,
+		hrmp_max_message_num_per_candidate,
+		validation_upgrade_cooldown,
+		validation_upgrade_delay,
+		max_pov_size,
+		max_downward_message_size,
+		hrmp_sender_deposit,
+		hrmp_recipient_deposit,
+		hrmp_channel_max_capacity,
+		hrmp_channel_max_total_size,
+		hrmp_max_parachain_inbound_channels,
+		hrmp_max_parachain_outbound_channels,
+		hrmp_channel_max_message_size,
+		code_retention_period,
+		on_demand_cores,
+		on_demand_retries,
+		group_rotation_frequency,
+		paras_availability_period,
+		scheduling_lookahead,
+		max_validators_per_core,
+		max_validators,
+		dispute_period,
+		dispute_post_conclusion_acceptance_period,
+		no_show_slots,
+		n_delay_tranches,
+		zeroth_delay_tranche_width,
+		needed_approvals,
+		relay_vrf_modulo_samples,
+		pvf_voting_ttl,
+		minimum_validation_upgrade_delay,
+		async_backing_params,
+		executor_params,
+		on_demand_queue_max_size,
+		on_demand_base_fee,
+		on_demand_fee_variability,
+		on_demand_target_queue_utilization,
+		on_demand_ttl,
+	],
+	added: { minimum_backing_votes: LEGACY_MIN_BACKING_VOTES },
+	removed: {},
+);
+
+/// Runs the configuration pallet's own consistency checks against a freshly translated V9
+/// config.
+///
+/// A legacy chain's stored V8 values were only ever validated under the old field set, so the
+/// translated V9 config could in principle be invalid. The one new field,
+/// `minimum_backing_votes`, is always set to `LEGACY_MIN_BACKING_VOTES`, so a failure comes from
+/// a value carried over from V8.
+/// Under `try-runtime` the inconsistency is left in place and `post_upgrade` hard-fails on it;
+/// otherwise we log an error and fall back to a safe default rather than letting a bad config
+/// take effect at the next session boundary.
+fn sanitize_host_config<T: Config>(
+	config: V9HostConfiguration<BlockNumberFor<T>>,
+) -> V9HostConfiguration<BlockNumberFor<T>> {
+	#[cfg(not(feature = "try-runtime"))]
+	if let Err(err) = config.check_consistency() {
+		log::error!(
+			target: configuration::LOG_TARGET,
+			"HostConfiguration migrated to V9 is not internally consistent ({:?}); \
+			clamping to a safe default",
+			err,
+		);
+		return Default::default()
+	}
 
+	config
+}
+
+pub(crate) fn migrate_to_v9<T: Config>() -> Weight {
 	let v8 = v8::ActiveConfig::<T>::get()
 		.defensive_proof("Could not decode old config")
 		.unwrap_or_default();
-	let v9 = translate(v8);
+	let v9 = sanitize_host_config::<T>(translate(v8));
 	v9::ActiveConfig::<T>::set(Some(v9));
 
 	// Allowed to be empty.
@@ -164,13 +263,35 @@ minimum_backing_votes                    : LEGACY_MIN_BACKING_VOTES
 	let mut pending_v9 = Vec::new();
 
 	for (session, v8) in pending_v8.into_iter() {
-		let v9 = translate(v8);
+		let v9 = sanitize_host_config::<T>(translate(v8));
 		pending_v9.push((session, v9));
 	}
-	v9::PendingConfigs::<T>::set(Some(pending_v9.clone()));
+	let weight = T::WeightInfo::migrate_host_config(pending_v9.len() as u32);
+	v9::PendingConfigs::<T>::set(Some(pending_v9));
 
-	let num_configs = (pending_v9.len() + 1) as u64;
-	T::DbWeight::get().reads_writes(num_configs, num_configs)
+	weight
+}
+
+/// Helpers for the `migrate_host_config` benchmark, kept next to the migration they exercise so
+/// the two can't drift apart.
+#[cfg(feature = "runtime-benchmarks")]
+pub(crate) mod benchmarking {
+	use super::*;
+
+	/// Populate the pre-migration (V8) storage with `n` pending configuration upgrades, plus an
+	/// active config, ready for [`super::migrate_to_v9`] to be run against.
+	pub(crate) fn build_pending_configs<T: Config>(n: u32) {
+		let config = V8HostConfiguration::<BlockNumberFor<T>>::default();
+		v8::ActiveConfig::<T>::set(Some(config.clone()));
+		let pending: Vec<_> =
+			(0..n as SessionIndex).map(|session| (session, config.clone())).collect();
+		v8::PendingConfigs::<T>::set(Some(pending));
+	}
+
+	/// Whether the post-migration (V9) active config was written.
+	pub(crate) fn active_config_is_set<T: Config>() -> bool {
+		v9::ActiveConfig::<T>::get().is_some()
+	}
 }
 
 #[cfg(test)]
@@ -246,57 +367,22 @@ mod tests {
 
 		new_test_ext(Default::default()).execute_with(|| {
 			// Implant the v8 version in the state.
-			v8::ActiveConfig::<Test>::set(Some(v8));
+			v8::ActiveConfig::<Test>::set(Some(v8.clone()));
 			v8::PendingConfigs::<Test>::set(Some(pending_configs));
 
 			migrate_to_v9::<Test>();
 
-			let v9 = v9::ActiveConfig::<Test>::get().unwrap();
-			let mut configs_to_check = v9::PendingConfigs::<Test>::get().unwrap();
-			configs_to_check.push((0, v9.clone()));
-
-			for (_, v8) in configs_to_check {
-				#[rustfmt::skip]
-				{
-					assert_eq!(v8.max_code_size                            , v9.max_code_size);
-					assert_eq!(v8.max_head_data_size                       , v9.max_head_data_size);
-					assert_eq!(v8.max_upward_queue_count                   , v9.max_upward_queue_count);
-					assert_eq!(v8.max_upward_queue_size                    , v9.max_upward_queue_size);
-					assert_eq!(v8.max_upward_message_size                  , v9.max_upward_message_size);
-					assert_eq!(v8.max_upward_message_num_per_candidate     , v9.max_upward_message_num_per_candidate);
-					assert_eq!(v8.hrmp_max_message_num_per_candidate       , v9.hrmp_max_message_num_per_candidate);
-					assert_eq!(v8.validation_upgrade_cooldown              , v9.validation_upgrade_cooldown);
-					assert_eq!(v8.validation_upgrade_delay                 , v9.validation_upgrade_delay);
-					assert_eq!(v8.max_pov_size                             , v9.max_pov_size);
-					assert_eq!(v8.max_downward_message_size                , v9.max_downward_message_size);
-					assert_eq!(v8.hrmp_max_parachain_outbound_channels     , v9.hrmp_max_parachain_outbound_channels);
-					assert_eq!(v8.hrmp_sender_deposit                      , v9.hrmp_sender_deposit);
-					assert_eq!(v8.hrmp_recipient_deposit                   , v9.hrmp_recipient_deposit);
-					assert_eq!(v8.hrmp_channel_max_capacity                , v9.hrmp_channel_max_capacity);
-					assert_eq!(v8.hrmp_channel_max_total_size              , v9.hrmp_channel_max_total_size);
-					assert_eq!(v8.hrmp_max_parachain_inbound_channels      , v9.hrmp_max_parachain_inbound_channels);
-					assert_eq!(v8.hrmp_channel_max_message_size            , v9.hrmp_channel_max_message_size);
-					assert_eq!(v8.code_retention_period                    , v9.code_retention_period);
-					assert_eq!(v8.on_demand_cores                          , v9.on_demand_cores);
-					assert_eq!(v8.on_demand_retries                        , v9.on_demand_retries);
-					assert_eq!(v8.group_rotation_frequency                 , v9.group_rotation_frequency);
-					assert_eq!(v8.paras_availability_period                , v9.paras_availability_period);
-					assert_eq!(v8.scheduling_lookahead                     , v9.scheduling_lookahead);
-					assert_eq!(v8.max_validators_per_core                  , v9.max_validators_per_core);
-					assert_eq!(v8.max_validators                           , v9.max_validators);
-					assert_eq!(v8.dispute_period                           , v9.dispute_period);
-					assert_eq!(v8.no_show_slots                            , v9.no_show_slots);
-					assert_eq!(v8.n_delay_tranches                         , v9.n_delay_tranches);
-					assert_eq!(v8.zeroth_delay_tranche_width               , v9.zeroth_delay_tranche_width);
-					assert_eq!(v8.needed_approvals                         , v9.needed_approvals);
-					assert_eq!(v8.relay_vrf_modulo_samples                 , v9.relay_vrf_modulo_samples);
-					assert_eq!(v8.pvf_voting_ttl                           , v9.pvf_voting_ttl);
-					assert_eq!(v8.minimum_validation_upgrade_delay         , v9.minimum_validation_upgrade_delay);
-					assert_eq!(v8.async_backing_params.allowed_ancestry_len, v9.async_backing_params.allowed_ancestry_len);
-					assert_eq!(v8.async_backing_params.max_candidate_depth , v9.async_backing_params.max_candidate_depth);
-					assert_eq!(v8.executor_params						   , v9.executor_params);
-				    assert_eq!(v8.minimum_backing_votes					   , v9.minimum_backing_votes);
-				}; // ; makes this a statement. `rustfmt::skip` cannot be put on an expression.
+			let post_active = v9::ActiveConfig::<Test>::get().unwrap();
+			let post_pending = v9::PendingConfigs::<Test>::get().unwrap();
+
+			// Every post-migration entry (the active config and each pending one) was
+			// translated from the very same pre-migration `v8`, so each is checked against it
+			// in turn. Driven by the same field list the migration itself translates from, so
+			// this can't silently drift from what `migrate_to_v9` actually copies.
+			for post in sp_std::iter::once(&post_active).chain(post_pending.iter().map(|(_, c)| c))
+			{
+				assert_fields_preserved(&v8, post);
+				assert_eq!(post.minimum_backing_votes, LEGACY_MIN_BACKING_VOTES);
 			}
 		});
 	}
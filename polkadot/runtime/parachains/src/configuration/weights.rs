@@ -0,0 +1,62 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for `parachains_configuration`.
+//!
+//! This only covers `migrate_host_config`, the one extrinsic-adjacent operation this crate
+//! actually benchmarks (see `migration::benchmarking`). The pallet's `set_*` extrinsics have
+//! their own, separately benchmarked `WeightInfo` entries upstream; they're out of scope here
+//! and aren't reproduced with placeholder numbers.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for `parachains_configuration`.
+pub trait WeightInfo {
+	fn migrate_host_config(n: u32) -> Weight;
+}
+
+/// Weights for `parachains_configuration` using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	/// Storage: `Configuration::PendingConfigs` (r:1 w:1)
+	/// Storage: `Configuration::ActiveConfig` (r:1 w:1)
+	///
+	/// The range of component `n` is `[0, 1000]`.
+	fn migrate_host_config(n: u32) -> Weight {
+		Weight::from_parts(5_000_000, 3)
+			.saturating_add(Weight::from_parts(700_000, 0).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+}
+
+// For backwards compatibility and tests.
+impl WeightInfo for () {
+	/// Storage: `Configuration::PendingConfigs` (r:1 w:1)
+	/// Storage: `Configuration::ActiveConfig` (r:1 w:1)
+	///
+	/// The range of component `n` is `[0, 1000]`.
+	fn migrate_host_config(n: u32) -> Weight {
+		Weight::from_parts(5_000_000, 3)
+			.saturating_add(Weight::from_parts(700_000, 0).saturating_mul(n as u64))
+			.saturating_add(RocksDbWeight::get().reads(2_u64))
+			.saturating_add(RocksDbWeight::get().writes(2_u64))
+	}
+}